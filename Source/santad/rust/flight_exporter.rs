@@ -0,0 +1,126 @@
+// Copyright 2025 North Pole Security, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{sink::Sink, traits::ArrowTable};
+use arrow::{array::RecordBatch, datatypes::SchemaRef, error::ArrowError};
+use arrow_flight::{
+    encode::FlightDataEncoderBuilder, flight_service_client::FlightServiceClient, FlightDescriptor,
+};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use std::sync::Arc;
+use tonic::transport::{Channel, Endpoint};
+
+/// Streams flushed `RecordBatch`es to a remote collector over Arrow Flight's
+/// `DoPut`, so downstream analytics can run OLAP-style queries on live
+/// telemetry without Santa first materializing Parquet files.
+///
+/// Batches are handed off through a bounded channel: if the collector falls
+/// behind, [FlightExporter::write_batch] blocks rather than buffering
+/// unboundedly in memory. On a transient failure the next
+/// [FlightExporter::write_batch] call reconnects and re-sends the schema
+/// message before resuming batches.
+pub struct FlightExporter {
+    endpoint: Endpoint,
+    descriptor: FlightDescriptor,
+    schema: SchemaRef,
+    max_in_flight: usize,
+    sender: Option<mpsc::Sender<RecordBatch>>,
+}
+
+impl FlightExporter {
+    /// Prepares an exporter for table `T` that will connect to `endpoint`
+    /// under flight path `descriptor` on first use. Up to `max_in_flight`
+    /// batches may be queued for the collector before
+    /// [FlightExporter::write_batch] applies backpressure.
+    pub fn new<T: ArrowTable>(endpoint: Endpoint, descriptor: FlightDescriptor, max_in_flight: usize) -> Self {
+        Self {
+            endpoint,
+            descriptor,
+            schema: Arc::new(T::table_schema()),
+            max_in_flight,
+            sender: None,
+        }
+    }
+
+    /// Sends `batch` to the collector, connecting (or reconnecting after a
+    /// prior failure) and re-sending the schema message first if needed.
+    pub async fn write_batch(&mut self, batch: RecordBatch) -> Result<(), ArrowError> {
+        if self.sender.is_none() {
+            self.connect().await?;
+        }
+        // The DoPut stream task may have died since the last send; drop it
+        // and reconnect once (which re-sends the schema message) before
+        // giving up on this batch.
+        if self.send(batch.clone()).await.is_err() {
+            self.sender = None;
+            self.connect().await?;
+            self.send(batch)
+                .await
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, batch: RecordBatch) -> Result<(), mpsc::SendError> {
+        match &mut self.sender {
+            Some(sender) => sender.send(batch).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn connect(&mut self) -> Result<(), ArrowError> {
+        let channel: Channel = self
+            .endpoint
+            .connect()
+            .await
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+        let mut client = FlightServiceClient::new(channel);
+
+        let (tx, rx) = mpsc::channel::<RecordBatch>(self.max_in_flight);
+        // The encoder yields `Result<FlightData, FlightError>` (encoding a
+        // batch can itself fail), but `do_put` requires a request stream of
+        // plain `FlightData`. Stop the stream on the first encoding error
+        // rather than unwrapping: ending the request stream early makes the
+        // next `write_batch`'s `send` fail, which is already handled by the
+        // reconnect-on-send-failure path in `write_batch` above.
+        let flight_data = FlightDataEncoderBuilder::new()
+            .with_schema(self.schema.clone())
+            .with_flight_descriptor(Some(self.descriptor.clone()))
+            .build(rx.map(Ok))
+            .take_while(|r| futures::future::ready(r.is_ok()))
+            .map(|r| r.expect("checked by take_while"));
+
+        let mut put_results = client
+            .do_put(flight_data)
+            .await
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?
+            .into_inner();
+
+        // `do_put` is a client-streaming call: the request body (`rx`/`tx`)
+        // and the response stream are tied together, and tonic/h2 reset the
+        // HTTP/2 stream if the response is dropped before it's fully
+        // consumed. Keep it alive by draining it for the life of the
+        // connection; a `PutResult` isn't meaningful to us here.
+        tokio::spawn(async move { while put_results.next().await.is_some() {} });
+
+        self.sender = Some(tx);
+        Ok(())
+    }
+}
+
+impl Sink for FlightExporter {
+    async fn write_batch(&mut self, batch: RecordBatch) -> Result<(), ArrowError> {
+        self.write_batch(batch).await
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright 2025 North Pole Security, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{sink::Sink, traits::ArrowTable};
+use arrow::{array::RecordBatch, datatypes::SchemaRef, error::ArrowError};
+use parquet::{
+    arrow::arrow_writer::ArrowWriter,
+    basic::Compression,
+    file::properties::{WriterProperties, WriterPropertiesBuilder},
+    schema::types::ColumnPath,
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Controls when a [ParquetSink] closes its current output file and opens a
+/// new one. Checked before each batch is written (once the current file holds
+/// at least one row), so a single oversized batch can still push a file past
+/// these thresholds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RolloverPolicy {
+    /// Roll over once the current file holds at least this many rows.
+    pub max_rows: Option<usize>,
+    /// Roll over once the current file's in-memory batch data reaches this
+    /// many bytes. This is an estimate (see
+    /// [RecordBatch::get_array_memory_size]), not the on-disk file size.
+    pub max_bytes: Option<u64>,
+}
+
+impl RolloverPolicy {
+    /// Never rolls over; all batches go to a single file.
+    pub const NEVER: Self = Self {
+        max_rows: None,
+        max_bytes: None,
+    };
+}
+
+/// Writes the `RecordBatch`es flushed from a [crate::traits::TableBuilder]
+/// for table `T` to a sequence of Parquet files under `dir`, rolling over to
+/// a new file per `rollover`.
+///
+/// Nested `Struct` and `List` columns (as emitted by the `arrow_table` macro)
+/// are written as-is: this type never flattens a batch before handing it to
+/// [ArrowWriter], so `arrow-rs` is the one computing the Parquet definition
+/// and repetition levels for those columns, rather than Santa reimplementing
+/// that encoding.
+pub struct ParquetSink {
+    dir: PathBuf,
+    prefix: String,
+    schema: SchemaRef,
+    properties: WriterProperties,
+    rollover: RolloverPolicy,
+    writer: ArrowWriter<File>,
+    sequence: u64,
+    rows_in_file: usize,
+    bytes_in_file: u64,
+}
+
+impl ParquetSink {
+    /// Opens the first output file for table `T` in `dir`. File names are
+    /// `{prefix}-{sequence:08}.parquet`, with `sequence` starting at 0 and
+    /// incrementing on every rollover.
+    pub fn new<T: ArrowTable>(
+        dir: impl Into<PathBuf>,
+        prefix: impl Into<String>,
+        properties: WriterProperties,
+        rollover: RolloverPolicy,
+    ) -> Result<Self, ArrowError> {
+        let dir = dir.into();
+        let prefix = prefix.into();
+        let schema = Arc::new(T::table_schema());
+        let writer = Self::open(&dir, &prefix, 0, schema.clone(), &properties)?;
+        Ok(Self {
+            dir,
+            prefix,
+            schema,
+            properties,
+            rollover,
+            writer,
+            sequence: 0,
+            rows_in_file: 0,
+            bytes_in_file: 0,
+        })
+    }
+
+    fn open(
+        dir: &Path,
+        prefix: &str,
+        sequence: u64,
+        schema: SchemaRef,
+        properties: &WriterProperties,
+    ) -> Result<ArrowWriter<File>, ArrowError> {
+        let path = dir.join(format!("{prefix}-{sequence:08}.parquet"));
+        let file = File::create(&path)
+            .map_err(|e| ArrowError::IoError(format!("creating {}: {e}", path.display()), e))?;
+        ArrowWriter::try_new(file, schema, Some(properties.clone())).map_err(ArrowError::from)
+    }
+
+    /// Writes `batch` to the currently open file, rolling over first if
+    /// `rollover` says the current file is already full.
+    pub fn write_batch(&mut self, batch: RecordBatch) -> Result<(), ArrowError> {
+        if self.rows_in_file > 0 && self.should_rollover() {
+            self.rollover()?;
+        }
+        self.rows_in_file += batch.num_rows();
+        self.bytes_in_file += batch.get_array_memory_size() as u64;
+        self.writer.write(&batch).map_err(ArrowError::from)
+    }
+
+    fn should_rollover(&self) -> bool {
+        self.rollover
+            .max_rows
+            .is_some_and(|max| self.rows_in_file >= max)
+            || self
+                .rollover
+                .max_bytes
+                .is_some_and(|max| self.bytes_in_file >= max)
+    }
+
+    fn rollover(&mut self) -> Result<(), ArrowError> {
+        self.sequence += 1;
+        let next = Self::open(
+            &self.dir,
+            &self.prefix,
+            self.sequence,
+            self.schema.clone(),
+            &self.properties,
+        )?;
+        std::mem::replace(&mut self.writer, next)
+            .close()
+            .map_err(ArrowError::from)?;
+        self.rows_in_file = 0;
+        self.bytes_in_file = 0;
+        Ok(())
+    }
+
+    /// Flushes and closes the currently open file. Callers should invoke this
+    /// on orderly shutdown; an unclosed writer's last row group may not be
+    /// durable.
+    pub fn close(self) -> Result<(), ArrowError> {
+        self.writer.close().map_err(ArrowError::from)?;
+        Ok(())
+    }
+}
+
+impl Sink for ParquetSink {
+    async fn write_batch(&mut self, batch: RecordBatch) -> Result<(), ArrowError> {
+        self.write_batch(batch)
+    }
+}
+
+/// Builds [WriterProperties] with a default compression codec plus
+/// per-column overrides, so callers can e.g. leave low-entropy columns on a
+/// cheap codec and spend zstd's extra CPU only on the columns that benefit.
+///
+/// Each `ColumnPath` in `column_compression` must have one part per level of
+/// nesting down to the leaf column (e.g. [nested_column_path]`(&["common",
+/// "boot_uuid"])` for a `boot_uuid` field inside a `common` struct, as
+/// `arrow_table`'s nested `StructBuilder` columns are laid out in
+/// [ArrowTable::table_schema]). `WriterProperties` keys its per-column
+/// overrides on these parts, not on the dotted display string, so e.g.
+/// `ColumnPath::from("common.boot_uuid")` is a *different*, non-matching key
+/// that silently falls back to `default_compression` for that column.
+pub fn writer_properties(
+    default_compression: Compression,
+    column_compression: impl IntoIterator<Item = (ColumnPath, Compression)>,
+    max_row_group_rows: usize,
+) -> WriterProperties {
+    let mut builder: WriterPropertiesBuilder = WriterProperties::builder()
+        .set_compression(default_compression)
+        .set_max_row_group_size(max_row_group_rows);
+    for (column, compression) in column_compression {
+        builder = builder.set_column_compression(column, compression);
+    }
+    builder.build()
+}
+
+/// Builds a [ColumnPath] from explicit path segments (one per level of
+/// nesting down to the leaf column), so callers don't have to reach for
+/// `ColumnPath::from(&str)` — which wraps the *whole* string as a single
+/// part and therefore never matches a real nested leaf's path.
+pub fn nested_column_path(parts: &[&str]) -> ColumnPath {
+    ColumnPath::new(parts.iter().map(|p| p.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::StructArray,
+        datatypes::{DataType, Field, Fields, Schema},
+    };
+    use parquet::arrow::arrow_to_parquet_schema;
+
+    /// A `ColumnPath` built with [nested_column_path] must match the path
+    /// Parquet's own schema walker assigns to the corresponding nested leaf
+    /// column — that's the whole point of not using `ColumnPath::from(&str)`
+    /// (a dotted string like `"common.boot_uuid"` builds a *different*,
+    /// single-part path that silently never matches, as `set_column_compression`
+    /// keys on path parts rather than the dotted display string).
+    #[test]
+    fn nested_column_path_matches_parquet_schema_walker() {
+        let schema = Schema::new(vec![Field::new(
+            "common",
+            DataType::Struct(Fields::from(vec![Field::new("boot_uuid", DataType::Utf8, true)])),
+            true,
+        )]);
+        let parquet_schema = arrow_to_parquet_schema(&schema).unwrap();
+        let leaf_path = parquet_schema.columns()[0].path().clone();
+
+        assert_eq!(leaf_path, nested_column_path(&["common", "boot_uuid"]));
+
+        let overridden = Compression::ZSTD(Default::default());
+        let properties = writer_properties(
+            Compression::UNCOMPRESSED,
+            [(nested_column_path(&["common", "boot_uuid"]), overridden)],
+            1024,
+        );
+        assert_eq!(properties.compression(&leaf_path), overridden);
+    }
+
+    fn common_batch(boot_uuid: &str) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "common",
+            DataType::Struct(Fields::from(vec![Field::new("boot_uuid", DataType::Utf8, true)])),
+            true,
+        )]));
+        let boot_uuid_array: arrow::array::ArrayRef = Arc::new(arrow::array::StringArray::from(vec![boot_uuid]));
+        let common = StructArray::from(vec![(
+            Arc::new(Field::new("boot_uuid", DataType::Utf8, true)),
+            boot_uuid_array,
+        )]);
+        RecordBatch::try_new(schema, vec![Arc::new(common)]).unwrap()
+    }
+
+    struct CommonTable;
+
+    impl ArrowTable for CommonTable {
+        fn table_schema() -> Schema {
+            Schema::new(vec![Field::new(
+                "common",
+                DataType::Struct(Fields::from(vec![Field::new("boot_uuid", DataType::Utf8, true)])),
+                true,
+            )])
+        }
+
+        fn builders(
+            _cap: usize,
+            _list_items: usize,
+            _string_len: usize,
+            _binary_len: usize,
+        ) -> Vec<Box<dyn arrow::array::ArrayBuilder>> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn rolls_over_once_max_rows_is_reached() {
+        let dir = tempfile::tempdir().unwrap();
+        let rollover = RolloverPolicy {
+            max_rows: Some(1),
+            max_bytes: None,
+        };
+        let mut sink = ParquetSink::new::<CommonTable>(
+            dir.path(),
+            "test",
+            WriterProperties::builder().build(),
+            rollover,
+        )
+        .unwrap();
+
+        sink.write_batch(common_batch("boot-1")).unwrap();
+        sink.write_batch(common_batch("boot-2")).unwrap();
+        sink.close().unwrap();
+
+        let mut files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec!["test-00000000.parquet", "test-00000001.parquet"]
+        );
+    }
+
+    #[test]
+    fn does_not_roll_over_under_max_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let rollover = RolloverPolicy {
+            max_rows: Some(10),
+            max_bytes: None,
+        };
+        let mut sink = ParquetSink::new::<CommonTable>(
+            dir.path(),
+            "test",
+            WriterProperties::builder().build(),
+            rollover,
+        )
+        .unwrap();
+
+        sink.write_batch(common_batch("boot-1")).unwrap();
+        sink.write_batch(common_batch("boot-2")).unwrap();
+        sink.close().unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+    }
+}
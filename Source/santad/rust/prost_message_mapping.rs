@@ -0,0 +1,70 @@
+// Copyright 2025 North Pole Security, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A manual per-field mapping helper for appending `prost::Message` values
+//! onto the `TableBuilder`s generated by `#[rednose_macro::arrow_table]`.
+//!
+//! [map_prost_message] does **not** walk a message's fields for you:
+//! `prost::Message` doesn't expose field names or a generic way to iterate
+//! its fields, and `macro_rules!` can't inspect a struct's fields either. The
+//! caller still lists one `append_{column}` call per mapped field, exactly
+//! as hand-written code would. What this macro saves is the
+//! `autocomplete_row` boilerplate repeated at every call site, plus a static
+//! check that the message type is actually a `prost::Message` rather than
+//! silently accepting any type.
+//!
+//! A real field-walking bridge would need a derive macro with access to both
+//! the message's and the table's field lists (e.g. a proc-macro alongside
+//! `arrow_table` in rednose_macro). That's a bigger change to an external
+//! crate and is tracked as future work, not attempted here.
+
+/// Generates an `append_message(&mut self, msg: &$message)` method on
+/// `$builder` (a `TableBuilder` produced by `#[arrow_table]`) that appends
+/// one `prost::Message` to the next row from an explicit, hand-listed set of
+/// `append_{column}` calls.
+///
+/// Each entry in the body is an `append_{column}` call on `$builder`, with
+/// `$msg` bound to the message parameter so the call can reach into its
+/// fields. After the listed columns are appended,
+/// [crate::traits::autocomplete_row] fills in anything left unset (nested
+/// `TableBuilder` columns should recurse with their own
+/// `map_prost_message!` rather than being listed here).
+///
+/// ```ignore
+/// map_prost_message!(CommonBuilder, proto::Common, msg, {
+///     append_boot_uuid(msg.boot_uuid.clone()),
+/// });
+/// ```
+#[macro_export]
+macro_rules! map_prost_message {
+    ($builder:ty, $message:ty, $msg:ident, { $($append_fn:ident($field:expr)),+ $(,)? }) => {
+        impl $builder {
+            /// Appends one row built from `msg`'s fields, then autocompletes
+            /// any column this mapping didn't cover.
+            pub fn append_message(&mut self, $msg: &$message) {
+                fn assert_is_prost_message<M: ::prost::Message>() {}
+                assert_is_prost_message::<$message>();
+
+                $( self.$append_fn($field); )+
+                // A mapping that covers every column leaves nothing to
+                // autocomplete, and `autocomplete_row` errors on a complete row.
+                let (complete, incomplete) = $crate::traits::TableBuilder::row_count(self);
+                if complete != incomplete {
+                    $crate::traits::autocomplete_row(self)
+                        .expect("map_prost_message could not autocomplete the row");
+                }
+            }
+        }
+    };
+}
@@ -22,5 +22,10 @@ use std::{collections::HashMap, time::Duration};
 
 #[arrow_table]
 pub struct Common {
+    // TODO: One value per boot, repeated on every row until the next boot —
+    // a prime candidate for dictionary encoding rather than plain UTF-8,
+    // once `arrow_table` supports a dictionary column attribute (see the
+    // TODO and `append_dictionary_value` in traits.rs). Stored as plain
+    // UTF-8 for now.
     pub boot_uuid: String,
 }
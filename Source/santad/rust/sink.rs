@@ -0,0 +1,36 @@
+// Copyright 2025 North Pole Security, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::{array::RecordBatch, error::ArrowError};
+use std::future::Future;
+
+/// Something that a flushed `RecordBatch` can be durably handed off to, such
+/// as [crate::parquet_sink::ParquetSink] or
+/// [crate::flight_exporter::FlightExporter].
+///
+/// This is the common interface [crate::spool::SpooledSink] wraps: it is
+/// generic over `Sink` so the write-ahead spool composes with whichever
+/// sink(s) a given table is configured to use.
+pub trait Sink {
+    /// Hands `batch` off to this sink. Returning `Ok` means the sink has
+    /// accepted the batch; it does not by itself mean the batch is durable
+    /// anywhere other sinks can rely on (see [crate::spool::SpooledSink] for
+    /// that guarantee).
+    ///
+    /// Desugared from `async fn` (rather than using one directly) because
+    /// `async fn` in a public trait can't express the `Send` bound that
+    /// spawning a sink's future onto a worker task requires; see
+    /// https://github.com/rust-lang/rust/issues/100013.
+    fn write_batch(&mut self, batch: RecordBatch) -> impl Future<Output = Result<(), ArrowError>> + Send;
+}
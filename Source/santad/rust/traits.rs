@@ -24,9 +24,22 @@
 // For now, leaving these trait definitions in Santa to mitigate namespace
 // issues until the rednose proc macro can be rewritten.
 
+// TODO:
+// `#[arrow(dictionary)]` on a String (or Binary) field is meant to tell the
+// `arrow_table` macro to emit a `StringDictionaryBuilder` for that column
+// instead of a plain `StringBuilder`, for low-cardinality columns like
+// `Common::boot_uuid` that would otherwise repeat the same bytes on every
+// row. This requires the corresponding `append_{column}`/`append_null`
+// codegen to go through the dictionary builder's key/value nulling instead
+// of `StringBuilder::append_null`. That codegen lives in rednose_macro (see
+// above), not here, and has not been updated to support the attribute yet.
+// [append_dictionary_value] below is the null-handling primitive that
+// codegen would call into once it exists; the round-trip through
+// `ArrowWriter` is exercised by this file's tests in the meantime.
+
 use arrow::{
-    array::{ArrayBuilder, StructBuilder},
-    datatypes::Schema,
+    array::{ArrayBuilder, ListBuilder, StringDictionaryBuilder, StructBuilder},
+    datatypes::{ArrowDictionaryKeyType, Schema},
     error::ArrowError,
 };
 
@@ -61,6 +74,56 @@ pub trait ArrowTable {
     ) -> Vec<Box<dyn ArrayBuilder>>;
 }
 
+/// Distinguishes a null list from an empty-but-present list.
+///
+/// Arrow (and therefore Parquet) encodes these two states differently: a
+/// null list is marked null one definition level above an empty list, which
+/// is present but simply has zero items. Conflating the two corrupts the
+/// round-trip through Parquet, so [append_list_state] takes this explicitly
+/// rather than guessing from context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListState {
+    /// The list field itself is null. Only valid if the field is nullable.
+    Null,
+    /// The list is present but has no items.
+    Empty,
+}
+
+/// Appends `state` to `builder` without touching its values builder.
+///
+/// [ListState::Null] appends a null list, [ListState::Empty] appends a list
+/// that is present but has had no values pushed onto it since the last
+/// append. The TableBuilders generated by `arrow_table` do not call this:
+/// they have no `append_null_{column}` / `append_empty_{column}` methods for
+/// List columns.
+pub fn append_list_state<T: ArrayBuilder>(builder: &mut ListBuilder<T>, state: ListState) {
+    match state {
+        ListState::Null => builder.append_null(),
+        ListState::Empty => builder.append(true),
+    }
+}
+
+/// Appends `value` to a dictionary-encoded string column, going through the
+/// dictionary builder's own key/value nulling rather than
+/// `StringBuilder::append_null` (which doesn't apply here: a dictionary
+/// column's null is a null key, not a null dictionary value).
+///
+/// This is what the generated `append_{column}` method for an
+/// `#[arrow(dictionary)]` field should reduce to once `arrow_table` supports
+/// the attribute (see the TODO above): `Some(value)` appends (and
+/// deduplicates) a value, `None` appends a null row with no value.
+pub fn append_dictionary_value<K: ArrowDictionaryKeyType>(
+    builder: &mut StringDictionaryBuilder<K>,
+    value: Option<&str>,
+) {
+    match value {
+        Some(value) => {
+            builder.append_value(value);
+        }
+        None => builder.append_null(),
+    }
+}
+
 /// For each schema table, the [rednose_macro::arrow_table] macro generates an
 /// implementation of TableBuilder, named "{table_name}Builder". This trait is
 /// used to build Arrow RecordBatches from data in the table schema.
@@ -72,6 +135,10 @@ pub trait ArrowTable {
 /// * append_{column_name}: Appends a concretely-typed value to the column.
 /// * {column_name}: If the column is a nested struct, returns the nested
 ///   TableBuilder that corresponds to that struct's schema table.
+///
+/// List (Vec<T>) columns get no separate null-list or empty-list methods;
+/// [append_list_state] appends either state to a column's ListBuilder
+/// directly (see [ListState] for why the distinction matters).
 pub trait TableBuilder {
     /// Construct a new builder for the given table. The arguments help
     /// calibrate how much memory is reserved for the builders.
@@ -100,6 +167,9 @@ pub trait TableBuilder {
     /// [StructBuilder::append_null] should behave. See
     /// https://github.com/apache/arrow-rs/issues/7192.)
     ///
+    /// List children are not appended through [append_list_state]; this
+    /// does not choose between [ListState::Null] and [ListState::Empty].
+    ///
     /// Calling this on the root TableBuilder will panic.
     fn append_null(&mut self);
 
@@ -120,7 +190,8 @@ pub trait TableBuilder {
     ///
     /// For most values, this will attempt to append a null, or fail if the
     /// column is not nullable. Structs are handled recursivelly. Lists are
-    /// appended in whatever state they're in.
+    /// appended in whatever state they're in; a null list and an empty list
+    /// are not told apart here (see [ListState]).
     fn autocomplete_row(&mut self, n: usize) -> Result<(), arrow::error::ArrowError>;
 
     /// Returns the number of columns in this builder.
@@ -200,3 +271,122 @@ fn debug_assert_row_counts<T: TableBuilder>(table_builder: &mut T) {
         debug_counts
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{Array, ArrayAccessor, DictionaryArray, Int32Array, Int32Builder, ListArray, RecordBatch},
+        datatypes::{DataType, Field, Int32Type},
+        ipc::{reader::StreamReader, writer::StreamWriter},
+    };
+    use std::sync::Arc;
+
+    /// A null list and an empty-but-present list must round-trip as
+    /// distinct states, not collapse into each other. This writes a batch
+    /// with a null list, an empty list and a populated list through an Arrow
+    /// IPC round-trip and checks each comes back as what it went in as. It
+    /// does not go through Parquet, so Parquet definition levels are not
+    /// exercised here.
+    #[test]
+    fn null_and_empty_lists_round_trip_distinctly() {
+        let mut builder = ListBuilder::new(Int32Builder::new());
+        append_list_state(&mut builder, ListState::Null);
+        append_list_state(&mut builder, ListState::Empty);
+        builder.values().append_slice(&[1, 2, 3]);
+        builder.append(true);
+        let list_array: ListArray = builder.finish();
+
+        let field = Field::new(
+            "items",
+            DataType::List(Arc::new(Field::new("item", DataType::Int32, true))),
+            true,
+        );
+        let schema = Arc::new(Schema::new(vec![field]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(list_array)]).unwrap();
+
+        let mut buf = Vec::new();
+        {
+            let mut writer = StreamWriter::try_new(&mut buf, &schema).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+        }
+        let mut reader = StreamReader::try_new(buf.as_slice(), None).unwrap();
+        let round_tripped = reader.next().unwrap().unwrap();
+        let column = round_tripped
+            .column(0)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+
+        assert!(column.is_null(0), "row 0 should be a null list");
+
+        assert!(!column.is_null(1), "row 1 should not be null");
+        assert_eq!(column.value(1).len(), 0, "row 1 should be an empty list");
+
+        assert!(!column.is_null(2), "row 2 should not be null");
+        let row_2 = column
+            .value(2)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap()
+            .clone();
+        assert_eq!(row_2, Int32Array::from(vec![1, 2, 3]));
+    }
+
+    /// Dictionary arrays are a known gap in the Arrow-Rust Parquet path, so
+    /// this writes a dictionary-encoded column with a null value through
+    /// [parquet::arrow::arrow_writer::ArrowWriter] and back and checks the
+    /// null and the deduplicated values all come back as they went in,
+    /// rather than assuming [append_dictionary_value] is correct from
+    /// reading arrow-rs's source.
+    #[test]
+    fn dictionary_column_round_trips_through_parquet_with_nulls() {
+        use arrow::array::StringDictionaryBuilder;
+        use parquet::arrow::{
+            arrow_reader::ParquetRecordBatchReaderBuilder, arrow_writer::ArrowWriter,
+        };
+
+        let mut builder: StringDictionaryBuilder<Int32Type> = StringDictionaryBuilder::new();
+        append_dictionary_value(&mut builder, Some("boot-1"));
+        append_dictionary_value(&mut builder, None);
+        append_dictionary_value(&mut builder, Some("boot-1"));
+        let array: DictionaryArray<Int32Type> = builder.finish();
+
+        let field = Field::new(
+            "boot_uuid",
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+            true,
+        );
+        let schema = Arc::new(Schema::new(vec![field]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(array)]).unwrap();
+
+        let mut buf = Vec::new();
+        {
+            let mut writer = ArrowWriter::try_new(&mut buf, schema, None).unwrap();
+            writer.write(&batch).unwrap();
+            writer.close().unwrap();
+        }
+
+        let mut reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(buf))
+            .unwrap()
+            .build()
+            .unwrap();
+        let round_tripped = reader.next().unwrap().unwrap();
+        let column = round_tripped
+            .column(0)
+            .as_any()
+            .downcast_ref::<DictionaryArray<Int32Type>>()
+            .unwrap();
+
+        assert!(!column.is_null(0));
+        assert!(column.is_null(1), "row 1 should be a null dictionary entry");
+        assert!(!column.is_null(2));
+
+        let values = column
+            .downcast_dict::<arrow::array::GenericStringArray<i32>>()
+            .unwrap();
+        assert_eq!(values.value(0), "boot-1");
+        assert_eq!(values.value(2), "boot-1");
+    }
+}